@@ -1,165 +1,108 @@
 use std::io::{stdin, stdout, BufRead, Write};
 
-use syntax::{
-    ast::{Node, ParseErrorType, Parseable},
-    lexer::{
-        matchers::{AnyChar, NumericChar, SpecificChar},
-        LexerError,
-    },
-};
-
-use crate::syntax::{
-    ast::Parser,
-    lexer::{IndexedCharIter, LexerStream},
-};
+use syntax::lexer::LexerError;
+
+use crate::syntax::ast::{program::Program, Parser};
 
 mod syntax;
-#[derive(Debug)]
-struct Number(f64);
-impl Parseable for Number {
-    fn parse<'a>(
-        state: &mut syntax::ast::Parser<'a>,
-    ) -> std::result::Result<syntax::ast::Node<Self>, syntax::ast::ParseError> {
-        let mut chars = String::new();
-
-        let mut negate = false;
-        if matches!(state.lexer().peek(), Ok((_, '-'))) {
-            state.lexer().eat::<'-'>()?;
-            negate = true;
-        }
 
-        let mut seen_dot = false;
-        loop {
-            if matches!(state.lexer().peek(), Ok((_, '.'))) {
-                if !seen_dot {
-                    state.lexer().eat::<'.'>()?;
-                    chars.push('.');
-                    seen_dot = true;
-                } else {
-                    return Err(state.err(ParseErrorType::ExtraDotInNumberLiteral));
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use crate::syntax::ast::{expr::Expr, program::Program, Parser};
 
-            match state.lexer().advance::<NumericChar>() {
-                Ok(c) => chars.push(c),
-                Err(e) => {
-                    break;
-                }
-            }
-        }
-        if chars.is_empty() {
-            return Err(state.err(syntax::ast::ParseErrorType::EmptyNumberLiteral));
-        }
+    #[test]
+    fn precedence_test() {
+        let mut parser = Parser::new("1+2*5");
+        assert_eq!(parser.parse::<Expr>().unwrap().evaluate(), 11.0);
+    }
 
-        let mut val = chars.parse::<f64>().unwrap();
-        if negate {
-            val = -val;
-        }
-        Ok(Node::new(Number(val), state.lexer().span()))
+    #[test]
+    fn long_expr() {
+        let mut parser = Parser::new("2/3*9");
+        assert_eq!(parser.parse::<Expr>().unwrap().evaluate(), 6.0);
     }
-}
-#[derive(Debug)]
-enum Factor {
-    Val(Number),
-    Parenthesis(Node<Term>),
-    Mul(Node<Factor>, Node<Factor>),
-    Div(Node<Factor>, Node<Factor>),
-}
-impl Factor {
-    pub fn evaluate(&self) -> f64 {
-        match self {
-            Factor::Parenthesis(v) => v.evaluate(),
-            Factor::Val(v) => v.0,
-            Factor::Mul(a, b) => a.evaluate() * b.evaluate(),
-            Factor::Div(a, b) => a.evaluate() / b.evaluate(),
-        }
+
+    #[test]
+    fn left_associative_subtraction() {
+        // Right-associative grouping would give 8-(4-2) = 6; the
+        // correct left-associative reading is (8-4)-2 = 2.
+        let mut parser = Parser::new("8-4-2");
+        assert_eq!(parser.parse::<Expr>().unwrap().evaluate(), 2.0);
     }
-}
 
-impl Parseable for Factor {
-    fn parse<'a>(
-        state: &mut Parser<'a>,
-    ) -> std::result::Result<Node<Self>, syntax::ast::ParseError> {
-        let num = if let Ok((_, '(')) = state.lexer().peek() {
-            state.lexer().eat::<'('>()?;
-            let in_parens = state.lexer().eat_until::<SpecificChar<')'>>()?;
-            Node::new(
-                Self::Parenthesis(state.parse_with_lexer(in_parens)?),
-                state.lexer().span(),
-            )
-        } else {
-            let num = state.parse::<Number>()?;
-            num.wrap(|v| Factor::Val(v))
-        };
-
-        match state.lexer().peek() {
-            Ok((_, '*')) => {
-                state.lexer().eat::<'*'>()?;
-                let num_two = state.parse()?;
-                Ok(state.node(Self::Mul(num, num_two)))
-            }
-            Ok((_, '/')) => {
-                state.lexer().eat::<'/'>()?;
-                let num_two = state.parse()?;
-                Ok(state.node(Self::Div(num, num_two)))
-            }
-            _ => Ok(num),
+    #[test]
+    fn string_literal_is_a_valid_expression() {
+        let mut parser = Parser::new(r#""hi\n""#);
+        match &*parser.parse::<Expr>().unwrap() {
+            Expr::Str(s) => assert_eq!(s, "hi\n"),
+            other => panic!("expected Expr::Str, got {other:?}"),
         }
     }
-}
-#[derive(Debug)]
-enum Term {
-    Val(Factor),
-    Add(Node<Term>, Node<Term>),
-    Sub(Node<Term>, Node<Term>),
-}
-impl Term {
-    pub fn evaluate(&self) -> f64 {
-        match self {
-            Term::Val(v) => v.evaluate(),
-            Term::Add(a, b) => a.evaluate() + b.evaluate(),
-            Term::Sub(a, b) => a.evaluate() - b.evaluate(),
-        }
+
+    #[test]
+    fn parenthesized_expr() {
+        let mut parser = Parser::new("(1+2)*5");
+        assert_eq!(parser.parse::<Expr>().unwrap().evaluate(), 15.0);
     }
-}
 
-impl Parseable for Term {
-    fn parse<'a>(
-        state: &mut Parser<'a>,
-    ) -> std::result::Result<Node<Self>, syntax::ast::ParseError> {
-        let num = state.parse::<Factor>()?;
-        let num = num.wrap(|v| Term::Val(v));
-
-        match state.lexer().peek() {
-            Ok((_, '+')) => {
-                state.lexer().eat::<'+'>()?;
-                let num_two = state.parse()?;
-                Ok(state.node(Self::Add(num, num_two)))
-            }
-            Ok((_, '-')) => {
-                state.lexer().eat::<'-'>()?;
-                let num_two = state.parse()?;
-                Ok(state.node(Self::Sub(num, num_two)))
-            }
-            _ => Ok(num),
-        }
+    #[test]
+    fn recovering_parse_collects_multiple_errors() {
+        // Two independent missing operands, one per parenthesized
+        // group. A fail-fast `parse` would only ever report the first.
+        let mut parser = Parser::new("(1+)+(2+)");
+        let errors = parser.parse_recovering::<Expr>().unwrap_err();
+        assert_eq!(errors.len(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{syntax::ast::Parser, Term};
+    #[test]
+    fn diagnostic_renders_a_caret_under_the_span() {
+        let source = "1 + ";
+        let mut parser = Parser::new(source);
+        let err = parser.parse::<Expr>().unwrap_err();
+        let rendered = err.diagnostic().render(source);
+        assert!(rendered.contains("1 | 1 + "));
+        assert!(rendered.contains('^'));
+    }
 
     #[test]
-    fn precedence_test() {
-        let mut parser = Parser::new("1+2*5");
-        assert_eq!(parser.parse::<Term>().unwrap().evaluate(), 11.0);
+    fn diagnostic_span_tracks_token_mode_parse_position() {
+        // Regression test: `Parser::tokens()` clones the lexer rather
+        // than sharing it, so `Parser::err` must read the live
+        // `TokenStream`'s position instead of the (frozen) char-mode
+        // one, or every token-mode error reports line 1 regardless of
+        // where parsing actually failed.
+        // `+` on line 1 forces the parser to keep recursing for its
+        // right-hand side, so the unclosed `(2 *` on line 2 is reached
+        // as part of the *same* expression rather than left as unread
+        // trailing data (which `parse::<Expr>` would silently ignore).
+        let source = "1 +\n(2 *";
+        let mut parser = Parser::new(source);
+        let err = parser.parse::<Expr>().unwrap_err();
+        let rendered = err.diagnostic().render(source);
+        assert!(rendered.contains("2 | (2 *"));
     }
 
     #[test]
-    fn long_expr() {
-        let mut parser = Parser::new("2/3*9");
-        assert_eq!(parser.parse::<Term>().unwrap().evaluate(), 6.0);
+    fn explicit_semicolons_separate_statements() {
+        let mut parser = Parser::new("1+2;3*4");
+        let program = parser.parse::<Program>().unwrap();
+        let results: Vec<f64> = program.statements.iter().map(|s| s.evaluate()).collect();
+        assert_eq!(results, vec![3.0, 12.0]);
+    }
+
+    #[test]
+    fn auto_terminate_splits_statements_on_newline() {
+        let mut parser = Parser::new("1+2\n3*4");
+        parser.set_auto_terminate(true);
+        let program = parser.parse::<Program>().unwrap();
+        let results: Vec<f64> = program.statements.iter().map(|s| s.evaluate()).collect();
+        assert_eq!(results, vec![3.0, 12.0]);
+    }
+
+    #[test]
+    fn without_auto_terminate_newline_is_trailing_data() {
+        let mut parser = Parser::new("1+2\n3*4");
+        assert!(parser.parse::<Program>().is_err());
     }
 }
 
@@ -178,37 +121,23 @@ fn main() -> std::result::Result<(), LexerError> {
             break;
         }
 
-        let mut parser = Parser::new(input.trim());
-        match parser.parse::<Term>() {
-            Ok(v) => {
-                if !parser.lexer().is_finished() {
-                    eprintln!("Trailing data @ {}", parser.lexer().position());
-                } else {
-                    println!("{}", v.evaluate());
+        let trimmed = input.trim();
+        let mut parser = Parser::new(trimmed);
+        parser.set_auto_terminate(true);
+        match parser.parse_recovering::<Program>() {
+            Ok(program) => {
+                for stmt in &program.statements {
+                    println!("{}", stmt.evaluate());
+                }
+            }
+            Err(errors) => {
+                for e in errors {
+                    eprintln!("{}", e.diagnostic().render(trimmed));
                 }
             }
-            Err(e) => eprintln!("Err: {}", e),
         }
         input.clear();
     }
 
-    // let mut v = LexerStream::new(IndexedCharIter::new("(1234)(2345)(22)".chars()));
-
-    // while v.advance::<SpecificChar<'('>>().is_ok() {
-    //     let mut second_stream = v.eat_until::<SpecificChar<')'>>().unwrap();
-
-    //     let mut chars = String::new();
-    //     loop {
-    //         match second_stream.advance::<NumericChar>() {
-    //             Ok(c) => chars.push(c),
-    //             Err(e) if e.is_eof() => break,
-    //             Err(e) => return Err(e),
-    //         }
-    //     }
-    //     println!("{}", chars);
-    // }
-
-    // println!("Hello WOrld");
-    //println!("Hello, world! {:?}", v.peek(None)?);
     Ok(())
 }
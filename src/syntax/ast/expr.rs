@@ -0,0 +1,203 @@
+//! A precedence-climbing (Pratt) expression parser. Replaces the old
+//! hand-written `Factor`/`Term` recursion, which hardcoded two
+//! precedence levels and was accidentally right-associative. Adding an
+//! operator is now a row in the binding-power tables below plus its
+//! arm in `Expr::evaluate`, instead of a new grammar type; a table row
+//! alone parses the operator, but evaluating it panics.
+
+use crate::syntax::token::Token;
+
+use super::{Node, ParseError, ParseErrorType, Parseable, Parser, Recoverable, Span};
+
+/// An arithmetic expression. `Unary`/`Binary` keep the source operator
+/// character around rather than pre-resolving to a closure so `Debug`
+/// output and error messages stay readable. `Error` is the sentinel a
+/// recovering parse substitutes for a sub-tree that failed to parse.
+#[derive(Debug)]
+pub enum Expr {
+    Number(f64),
+    Str(String),
+    Unary(char, Node<Expr>),
+    Binary(char, Node<Expr>, Node<Expr>),
+    Error,
+}
+
+impl Expr {
+    /// There's no string arithmetic yet, so a `Str` evaluates to `NAN`
+    /// just like the `Error` sentinel — it's a valid expression, but
+    /// not a valid number.
+    pub fn evaluate(&self) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Str(_) => f64::NAN,
+            Expr::Unary('-', v) => -v.evaluate(),
+            Expr::Unary(_, v) => v.evaluate(),
+            Expr::Binary('+', a, b) => a.evaluate() + b.evaluate(),
+            Expr::Binary('-', a, b) => a.evaluate() - b.evaluate(),
+            Expr::Binary('*', a, b) => a.evaluate() * b.evaluate(),
+            Expr::Binary('/', a, b) => a.evaluate() / b.evaluate(),
+            Expr::Binary(op, ..) => unreachable!("no evaluate arm for operator {op}"),
+            Expr::Error => f64::NAN,
+        }
+    }
+}
+
+impl Parseable for Expr {
+    fn parse<'a>(state: &mut Parser<'a>) -> Result<Node<Self>, ParseError> {
+        parse_expr(state, 0)
+    }
+}
+
+impl Recoverable for Expr {
+    fn error_node(_span: Span) -> Self {
+        Expr::Error
+    }
+}
+
+/// `left_bp < right_bp` makes an operator left-associative (ties at
+/// the same precedence bind to the left); `left_bp > right_bp` makes
+/// it right-associative.
+fn infix_binding_power(op: char) -> Option<(u8, u8)> {
+    match op {
+        '+' | '-' => Some((1, 2)),
+        '*' | '/' => Some((3, 4)),
+        _ => None,
+    }
+}
+
+fn prefix_binding_power(op: char) -> Option<u8> {
+    match op {
+        '+' | '-' => Some(5),
+        _ => None,
+    }
+}
+
+/// Parses a prefix atom, then repeatedly folds in infix operators
+/// whose left binding power is at least `min_bp`, recursing on the
+/// right-hand side with that operator's right binding power. Stopping
+/// as soon as `left_bp < min_bp` is what lets a single recursive
+/// routine implement arbitrary precedence and associativity.
+pub fn parse_expr<'a>(state: &mut Parser<'a>, min_bp: u8) -> Result<Node<Expr>, ParseError> {
+    let mut lhs = match parse_prefix(state) {
+        Ok(node) => node,
+        Err(e) if state.is_recovering() => recover(state, e),
+        Err(e) => return Err(e),
+    };
+
+    while let Ok(tok) = state.tokens().peek() {
+        let op = match **tok {
+            Token::Operator(c) => c,
+            _ => break,
+        };
+
+        let Some((left_bp, right_bp)) = infix_binding_power(op) else {
+            break;
+        };
+        if left_bp < min_bp {
+            break;
+        }
+
+        state.tokens().next()?;
+        let rhs = match parse_expr(state, right_bp) {
+            Ok(node) => node,
+            Err(e) if state.is_recovering() => recover(state, e),
+            Err(e) => return Err(e),
+        };
+        let span = Span::new(lhs.span().start, rhs.span().end);
+        lhs = Node::new(Expr::Binary(op, lhs, rhs), span);
+    }
+
+    Ok(lhs)
+}
+
+/// Peeks (rather than unconditionally consuming) the next token before
+/// deciding how to parse it. This matters for recovery: if the token
+/// isn't a valid atom, it's left in the stream for `recover`'s
+/// synchronization loop to see, instead of being silently eaten by
+/// this failed attempt — otherwise a stray `)` consumed here can never
+/// be found again, permanently losing the enclosing group's boundary.
+fn parse_prefix<'a>(state: &mut Parser<'a>) -> Result<Node<Expr>, ParseError> {
+    let (peeked, span) = match state.tokens().peek() {
+        Ok(tok) => ((**tok).clone(), tok.span()),
+        Err(e) => return Err(e.into()),
+    };
+
+    match peeked {
+        Token::Number(n) => {
+            state.tokens().next()?;
+            Ok(Node::new(Expr::Number(n), span))
+        }
+        Token::Str(s) => {
+            state.tokens().next()?;
+            Ok(Node::new(Expr::Str(s), span))
+        }
+        Token::Operator(op) if prefix_binding_power(op).is_some() => {
+            state.tokens().next()?;
+            let bp = prefix_binding_power(op).unwrap();
+            let rhs = match parse_expr(state, bp) {
+                Ok(node) => node,
+                Err(e) if state.is_recovering() => recover(state, e),
+                Err(e) => return Err(e),
+            };
+            let span = Span::new(span.start, rhs.span().end);
+            Ok(Node::new(Expr::Unary(op, rhs), span))
+        }
+        Token::Paren('(') => {
+            state.tokens().next()?;
+            let inner = match parse_expr(state, 0) {
+                Ok(node) => node,
+                Err(e) if state.is_recovering() => recover(state, e),
+                Err(e) => return Err(e),
+            };
+
+            match state.tokens().next() {
+                Ok(close) if matches!(&*close, Token::Paren(')')) => Ok(inner),
+                Ok(_) => {
+                    let e = state.err(ParseErrorType::ExpectedToken(")".to_string()));
+                    if state.is_recovering() {
+                        state.record_error(e);
+                        Ok(inner)
+                    } else {
+                        Err(e)
+                    }
+                }
+                Err(e) if state.is_recovering() => {
+                    state.record_error(e.into());
+                    Ok(inner)
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+        // Not a valid atom. Deliberately *not* consumed — see the
+        // function doc comment above.
+        _ => Err(state.err(ParseErrorType::UnexpectedToken)),
+    }
+}
+
+/// Records `e`, then advances the token stream to the next
+/// synchronization point — an infix operator or a closing paren,
+/// either of which is a safe place for the caller to resume folding —
+/// and returns an `Expr::error_node` sentinel covering the skipped
+/// span.
+fn recover<'a>(state: &mut Parser<'a>, e: ParseError) -> Node<Expr> {
+    state.record_error(e);
+
+    let start = match state.tokens().peek() {
+        Ok(tok) => tok.span(),
+        Err(_) => state.lexer().span(),
+    };
+    let mut end = start;
+
+    while let Ok(tok) = state.tokens().peek() {
+        match **tok {
+            Token::Operator(_) | Token::Paren(')') | Token::Eof => break,
+            _ => {
+                end = tok.span();
+                let _ = state.tokens().next();
+            }
+        }
+    }
+
+    let span = Span::new(start.start, end.end);
+    Node::new(Expr::error_node(span), span)
+}
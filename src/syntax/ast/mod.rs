@@ -6,6 +6,10 @@ use std::{
 use thiserror::Error;
 
 use super::lexer::{CharIndex, IndexedCharIter, LexerError, LexerStream};
+use super::token::TokenStream;
+
+pub mod expr;
+pub mod program;
 /// Represents a region of a file.
 #[derive(Debug, Clone, Copy)]
 pub struct Span {
@@ -72,18 +76,46 @@ pub trait Parseable: Sized {
     fn parse<'a>(state: &mut Parser<'a>) -> std::result::Result<Node<Self>, ParseError>;
 }
 
+/// A `Parseable` that can stand in for a sub-tree that failed to
+/// parse, so `Parser::parse_recovering` can keep going after an error
+/// instead of aborting the whole parse.
+pub trait Recoverable: Parseable {
+    fn error_node(span: Span) -> Self;
+}
+
 /// Represents the state of the parser. Primarily a wrapper for a
 /// changing `LexerStream` to allow nested parsing.
 pub struct Parser<'a> {
     stream: LexerStream<'a>,
+    tokens: Option<TokenStream<'a>>,
+    /// Diagnostics collected while `recovering` is set. Cleared at the
+    /// start of each `parse_recovering` call.
+    errors: Vec<ParseError>,
+    recovering: bool,
+    /// When set, `Program` treats a newline between statements as an
+    /// implicit `;`. Off by default so callers that want only the
+    /// explicit separator keep today's behavior.
+    auto_terminate: bool,
 }
 impl<'a> Parser<'a> {
     pub fn new(s: &'a str) -> Self {
         Self {
             stream: LexerStream::new(IndexedCharIter::new(s.chars())),
+            tokens: None,
+            errors: Vec::new(),
+            recovering: false,
+            auto_terminate: false,
         }
     }
 
+    pub fn auto_terminate(&self) -> bool {
+        self.auto_terminate
+    }
+
+    pub fn set_auto_terminate(&mut self, enabled: bool) {
+        self.auto_terminate = enabled;
+    }
+
     pub fn parse_with_lexer<T: Parseable>(
         &mut self,
         lexer: LexerStream<'a>,
@@ -109,11 +141,68 @@ impl<'a> Parser<'a> {
         &mut self.stream
     }
 
+    /// Token-mode entry point: lazily builds a `TokenStream` over the
+    /// current lexer position so a `Parseable` can match on `Token`
+    /// variants instead of scanning chars itself.
+    ///
+    /// This clones `self.stream` rather than sharing it, so `self.stream`
+    /// stops advancing once token mode is in use — a parse never mixes
+    /// char-mode and token-mode `Parseable`s, but that does mean `err`
+    /// below has to know which stream is actually live.
+    pub fn tokens(&mut self) -> &mut TokenStream<'a> {
+        self.tokens
+            .get_or_insert_with(|| TokenStream::new(self.stream.clone()))
+    }
+
+    /// Builds a `ParseError` at the parser's current position. Reads
+    /// that position from the `TokenStream` once one exists, since
+    /// `self.stream` stops advancing the moment `tokens()` takes over
+    /// (see its doc comment) — using `self.stream.span()`
+    /// unconditionally would freeze every token-mode error at the
+    /// parse's starting position.
     pub fn err(&self, e: ParseErrorType) -> ParseError {
-        ParseError {
-            span: self.stream.span(),
-            ty: e,
+        let span = match &self.tokens {
+            Some(tokens) => tokens.span(),
+            None => self.stream.span(),
+        };
+        ParseError { span, ty: e }
+    }
+
+    /// Whether a sub-parse should substitute an `error_node` and keep
+    /// going instead of propagating its `ParseError`. Checked by
+    /// `Parseable` impls that know how to recover (e.g. `Expr`).
+    pub fn is_recovering(&self) -> bool {
+        self.recovering
+    }
+
+    /// Records a diagnostic found while recovering. Only meaningful
+    /// between a `parse_recovering` call and its return.
+    pub fn record_error(&mut self, e: ParseError) {
+        self.errors.push(e);
+    }
+
+    /// Like `parse`, but on failure a `Recoverable` impl may swallow
+    /// the error (via `is_recovering`/`record_error`) and return an
+    /// `error_node` instead of bubbling it up. `Ok` is only returned
+    /// if the parse completed without *any* recorded diagnostics;
+    /// otherwise every diagnostic collected along the way is reported
+    /// together rather than just the first one.
+    pub fn parse_recovering<T: Recoverable>(
+        &mut self,
+    ) -> std::result::Result<Node<T>, Vec<ParseError>> {
+        self.errors.clear();
+        self.recovering = true;
+        let result = self.parse::<T>();
+        self.recovering = false;
+
+        if self.errors.is_empty() {
+            return result.map_err(|e| vec![e]);
         }
+
+        if let Err(e) = result {
+            self.errors.push(e);
+        }
+        Err(std::mem::take(&mut self.errors))
     }
 }
 
@@ -125,6 +214,18 @@ pub enum ParseErrorType {
     EmptyNumberLiteral,
     #[error("extra dot in number literal")]
     ExtraDotInNumberLiteral,
+    #[error("malformed number literal: {0}")]
+    MalformedNumber(String),
+    #[error("unexpected token")]
+    UnexpectedToken,
+    #[error("expected {0}")]
+    ExpectedToken(String),
+    #[error("unterminated string literal")]
+    UnterminatedString,
+    #[error("malformed escape sequence")]
+    MalformedEscapeSequence,
+    #[error("invalid unicode scalar in \\u{{...}} escape")]
+    InvalidUnicodeScalar,
 }
 
 #[derive(Debug, Error)]
@@ -141,9 +242,37 @@ impl Display for ParseError {
 
 impl From<LexerError> for ParseError {
     fn from(value: LexerError) -> Self {
+        let span = Span::new(value.position(), value.position());
+
+        // Number- and string-literal scanning live in the token layer
+        // (they need lookahead over the raw chars), but these
+        // diagnostics read better surfaced as their own
+        // `ParseErrorType` than generic `LexerError` wrapping.
+        let ty = match value.kind() {
+            super::lexer::LexerErrorType::EmptyNumberLiteral => {
+                Some(ParseErrorType::EmptyNumberLiteral)
+            }
+            super::lexer::LexerErrorType::ExtraDotInNumberLiteral => {
+                Some(ParseErrorType::ExtraDotInNumberLiteral)
+            }
+            super::lexer::LexerErrorType::MalformedNumber(reason) => {
+                Some(ParseErrorType::MalformedNumber(reason.clone()))
+            }
+            super::lexer::LexerErrorType::UnterminatedString => {
+                Some(ParseErrorType::UnterminatedString)
+            }
+            super::lexer::LexerErrorType::MalformedEscapeSequence => {
+                Some(ParseErrorType::MalformedEscapeSequence)
+            }
+            super::lexer::LexerErrorType::InvalidUnicodeScalar => {
+                Some(ParseErrorType::InvalidUnicodeScalar)
+            }
+            _ => None,
+        };
+
         Self {
-            span: Span::new(value.position(), value.position()),
-            ty: value.into(),
+            span,
+            ty: ty.unwrap_or(ParseErrorType::LexerError(value)),
         }
     }
 }
@@ -0,0 +1,89 @@
+use crate::syntax::token::Token;
+
+use super::{expr::Expr, Node, ParseError, ParseErrorType, Parseable, Parser, Recoverable, Span};
+
+/// A sequence of expressions, one per statement. Statements are
+/// separated either by an explicit `;` or, with
+/// `Parser::set_auto_terminate(true)`, by a newline following a
+/// complete expression — mirroring how a REPL line or an editor buffer
+/// with no trailing `;` is still a sequence of statements rather than
+/// one big "trailing data" error.
+#[derive(Debug)]
+pub struct Program {
+    pub statements: Vec<Node<Expr>>,
+}
+
+impl Parseable for Program {
+    fn parse<'a>(state: &mut Parser<'a>) -> Result<Node<Self>, ParseError> {
+        let start = state.tokens().peek()?.span();
+        let mut end = start;
+        let mut statements = Vec::new();
+
+        loop {
+            while matches!(state.tokens().peek(), Ok(tok) if **tok == Token::Operator(';')) {
+                state.tokens().next()?;
+            }
+
+            if matches!(state.tokens().peek(), Ok(tok) if **tok == Token::Eof) {
+                break;
+            }
+
+            let stmt = state.parse::<Expr>()?;
+            end = stmt.span();
+            statements.push(stmt);
+
+            // Snapshot the peeked token into an owned value first: the
+            // `auto_terminate` guard below needs its own call to
+            // `state.tokens()`, which can't happen while a `match` is
+            // still borrowing the token peek() returned.
+            let peeked = state.tokens().peek().map(|tok| (**tok).clone());
+            let newline_before =
+                state.auto_terminate() && state.tokens().newline_before(0)?;
+
+            match peeked {
+                Ok(Token::Eof) => break,
+                Ok(Token::Operator(';')) => {
+                    state.tokens().next()?;
+                }
+                _ if newline_before => {
+                    // A statement just finished and a line break
+                    // follows it: treat that as the separator without
+                    // consuming anything, same as an explicit `;`.
+                }
+                Ok(_) => {
+                    let e = state.err(ParseErrorType::ExpectedToken(
+                        "`;` or a newline between statements".to_string(),
+                    ));
+                    if state.is_recovering() {
+                        state.record_error(e);
+                        // Resynchronize at the next statement boundary
+                        // instead of aborting the whole parse, so a
+                        // recovering `Program` parse can still surface
+                        // the errors in every later statement too.
+                        while let Ok(tok) = state.tokens().peek() {
+                            match **tok {
+                                Token::Operator(';') | Token::Eof => break,
+                                _ => {
+                                    let _ = state.tokens().next();
+                                }
+                            }
+                        }
+                    } else {
+                        return Err(e);
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(Node::new(Program { statements }, Span::new(start.start, end.end)))
+    }
+}
+
+impl Recoverable for Program {
+    fn error_node(_span: Span) -> Self {
+        Program {
+            statements: Vec::new(),
+        }
+    }
+}
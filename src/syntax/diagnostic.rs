@@ -0,0 +1,71 @@
+//! Turns a `ParseError` plus the original source into a human-readable
+//! report, in the spirit of `highlight_error`-style crates:
+//!
+//! ```text
+//! 3 | 1 + (2 *
+//!   |         ^ unexpected token
+//! ```
+
+use super::ast::{ParseError, Span};
+
+/// A message anchored to one or more source `Span`s.
+pub struct Diagnostic {
+    pub message: String,
+    pub spans: Vec<Span>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            spans: vec![span],
+        }
+    }
+
+    /// Renders every span against `source`, one `line | ...` /
+    /// `caret` pair per span, each pair starting on its own line.
+    pub fn render(&self, source: &str) -> String {
+        let lines: Vec<&str> = source.split('\n').collect();
+        self.spans
+            .iter()
+            .map(|span| render_span(&lines, *span, &self.message))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Renders a single span. Multi-line spans are clamped to their first
+/// line (the line `span.start` falls on); a span sitting at
+/// end-of-line/EOF (`column == line.len()`) still gets a one-column
+/// caret rather than disappearing.
+fn render_span(lines: &[&str], span: Span, message: &str) -> String {
+    let line_text = lines.get(span.start.line).copied().unwrap_or("");
+    let line_len = line_text.chars().count();
+
+    let start_col = span.start.column.min(line_len);
+    let end_col = if span.end.line == span.start.line {
+        span.end.column.min(line_len).max(start_col)
+    } else {
+        line_len
+    };
+    let width = (end_col - start_col).max(1);
+
+    let gutter_label = (span.start.line + 1).to_string();
+    let blank_gutter = " ".repeat(gutter_label.len());
+
+    format!(
+        "{label} | {line}\n{blank} | {pad}{carets} {message}",
+        label = gutter_label,
+        line = line_text,
+        blank = blank_gutter,
+        pad = " ".repeat(start_col),
+        carets = "^".repeat(width),
+        message = message,
+    )
+}
+
+impl ParseError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(self.ty.to_string(), self.span)
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, iter::Enumerate, str::Chars};
+use std::{collections::VecDeque, fmt::Display, str::Chars};
 
 use thiserror::Error;
 
@@ -35,6 +35,101 @@ pub mod matchers {
         pub const VALUE: &'static dyn CharMatcher = &Self;
     }
 
+    #[derive(Clone, Copy)]
+    pub struct HexDigitChar;
+    impl CharMatcher for HexDigitChar {
+        fn is_match(&self, c: char) -> std::result::Result<(), String> {
+            if c.is_ascii_hexdigit() {
+                Ok(())
+            } else {
+                Err(format!("got non-hex-digit character {c}"))
+            }
+        }
+
+        fn dynamic() -> &'static dyn CharMatcher {
+            Self::VALUE
+        }
+    }
+    impl HexDigitChar {
+        pub const VALUE: &'static dyn CharMatcher = &Self;
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct BinaryDigitChar;
+    impl CharMatcher for BinaryDigitChar {
+        fn is_match(&self, c: char) -> std::result::Result<(), String> {
+            if c == '0' || c == '1' {
+                Ok(())
+            } else {
+                Err(format!("got non-binary-digit character {c}"))
+            }
+        }
+
+        fn dynamic() -> &'static dyn CharMatcher {
+            Self::VALUE
+        }
+    }
+    impl BinaryDigitChar {
+        pub const VALUE: &'static dyn CharMatcher = &Self;
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct OctalDigitChar;
+    impl CharMatcher for OctalDigitChar {
+        fn is_match(&self, c: char) -> std::result::Result<(), String> {
+            if ('0'..='7').contains(&c) {
+                Ok(())
+            } else {
+                Err(format!("got non-octal-digit character {c}"))
+            }
+        }
+
+        fn dynamic() -> &'static dyn CharMatcher {
+            Self::VALUE
+        }
+    }
+    impl OctalDigitChar {
+        pub const VALUE: &'static dyn CharMatcher = &Self;
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct AlphaChar;
+    impl CharMatcher for AlphaChar {
+        fn is_match(&self, c: char) -> std::result::Result<(), String> {
+            if c.is_alphabetic() || c == '_' {
+                Ok(())
+            } else {
+                Err(format!("got non-alphabetic character {c}"))
+            }
+        }
+
+        fn dynamic() -> &'static dyn CharMatcher {
+            Self::VALUE
+        }
+    }
+    impl AlphaChar {
+        pub const VALUE: &'static dyn CharMatcher = &Self;
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct AlphaNumericChar;
+    impl CharMatcher for AlphaNumericChar {
+        fn is_match(&self, c: char) -> std::result::Result<(), String> {
+            if c.is_alphanumeric() || c == '_' {
+                Ok(())
+            } else {
+                Err(format!("got non-alphanumeric character {c}"))
+            }
+        }
+
+        fn dynamic() -> &'static dyn CharMatcher {
+            Self::VALUE
+        }
+    }
+    impl AlphaNumericChar {
+        pub const VALUE: &'static dyn CharMatcher = &Self;
+    }
+
     #[derive(Clone, Copy)]
     pub struct AnyChar;
     impl CharMatcher for AnyChar {
@@ -94,6 +189,12 @@ impl CharIndex {
         clone
     }
 }
+
+impl Display for CharIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
 #[derive(Clone, Copy, Debug)]
 pub enum WhitespaceMode {
     Skip,
@@ -110,6 +211,17 @@ pub struct IndexedCharIter<'a> {
     chars: Chars<'a>,
     index: CharIndex,
     whitespace: WhitespaceMode,
+    /// Set when a `\n` is skipped by `WhitespaceMode::Skip` since the
+    /// last call to `take_crossed_newline`. Lets callers that only see
+    /// significant (non-whitespace) chars still notice line breaks,
+    /// e.g. for automatic statement separation.
+    crossed_newline: bool,
+    /// Set when *any* whitespace (not just `\n`) is skipped since the
+    /// last call to `take_crossed_whitespace`. Broader than
+    /// `crossed_newline` — a multi-char scan (number/ident) needs to
+    /// stop at any whitespace boundary, not just a line break, or it
+    /// folds the next token's leading chars into the one it's scanning.
+    crossed_whitespace: bool,
 }
 
 impl<'a> Iterator for IndexedCharIter<'a> {
@@ -117,13 +229,15 @@ impl<'a> Iterator for IndexedCharIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut next = self.chars.next()?;
-        match self.whitespace {
-            WhitespaceMode::Skip => {
-                while next.is_whitespace() {
-                    next = self.chars.next()?;
+        if let WhitespaceMode::Skip = self.whitespace {
+            while next.is_whitespace() {
+                if next == '\n' {
+                    self.crossed_newline = true;
                 }
+                self.crossed_whitespace = true;
+                self.index = self.index.advance(next);
+                next = self.chars.next()?;
             }
-            _ => (),
         }
         let og_index = self.index;
         self.index = self.index.advance(next);
@@ -141,8 +255,29 @@ impl<'a> IndexedCharIter<'a> {
             chars,
             index: Default::default(),
             whitespace: WhitespaceMode::Skip,
+            crossed_newline: false,
+            crossed_whitespace: false,
         }
     }
+
+    /// Reports and clears whether whitespace skipped since the last
+    /// call crossed a `\n`.
+    pub fn take_crossed_newline(&mut self) -> bool {
+        std::mem::replace(&mut self.crossed_newline, false)
+    }
+
+    /// Reports and clears whether *any* whitespace skipped since the
+    /// last call included at least one char. Reset once per token (see
+    /// `TokenStream::lex_one`) so a scan can tell whitespace crossed
+    /// *during* it from whitespace crossed before it started.
+    pub fn take_crossed_whitespace(&mut self) -> bool {
+        std::mem::replace(&mut self.crossed_whitespace, false)
+    }
+
+    /// Like `take_crossed_whitespace`, but doesn't clear the flag.
+    pub fn crossed_whitespace_pending(&self) -> bool {
+        self.crossed_whitespace
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -163,6 +298,10 @@ impl PeekState {
 pub struct LexerStream<'a> {
     chars: IndexedCharIter<'a>,
     peek: PeekState,
+    /// Extra lookahead beyond `peek`, filled on demand by `peek_n` and
+    /// drained (front-first) by `advance` once the stream catches up to
+    /// it. Each entry carries the `end` index it would leave behind.
+    lookahead: VecDeque<(PeekState, CharIndex)>,
     ty: LexerType,
     start: CharIndex,
     end: CharIndex,
@@ -179,6 +318,7 @@ impl<'a> LexerStream<'a> {
             end: chars.index(),
             chars,
             peek,
+            lookahead: VecDeque::new(),
             ty: LexerType::UntilEof,
         }
     }
@@ -190,10 +330,19 @@ impl<'a> LexerStream<'a> {
         }
     }
 
+    /// The read head's current position, i.e. where the last
+    /// `advance()` left off. Unlike `span()` (whose `start` is fixed at
+    /// construction), this tracks the live position — what a
+    /// point-in-time diagnostic span wants.
+    pub fn position(&self) -> CharIndex {
+        self.end
+    }
+
     pub fn eat_until<C: CharMatcher>(&mut self) -> LexerResult<LexerStream<'a>> {
         let new_lexer = LexerStream {
             chars: self.chars.clone(),
             peek: self.peek.clone(),
+            lookahead: self.lookahead.clone(),
             ty: LexerType::UntilEnd(C::dynamic()),
             start: self.start,
             end: self.end,
@@ -202,6 +351,27 @@ impl<'a> LexerStream<'a> {
         Ok(new_lexer)
     }
 
+    /// Whether whitespace skipped since the last call crossed a `\n`.
+    /// Used by token-mode statement separation to tell auto-inserted
+    /// terminators from ordinary spacing.
+    pub fn crossed_newline(&mut self) -> bool {
+        self.chars.take_crossed_newline()
+    }
+
+    /// Whether any whitespace (not just `\n`) was skipped to reach the
+    /// char currently in `peek`, or while `peek_n` filled its lookahead
+    /// beyond it. Used by multi-char scanners (numbers,
+    /// idents) to stop at any whitespace boundary mid-token instead of
+    /// folding the next token's leading characters into the same scan.
+    pub fn crossed_whitespace(&mut self) -> bool {
+        self.chars.take_crossed_whitespace()
+    }
+
+    /// Like `crossed_whitespace`, but doesn't clear the flag.
+    pub fn crossed_whitespace_pending(&self) -> bool {
+        self.chars.crossed_whitespace_pending()
+    }
+
     pub fn peek(&self) -> LexerResult<(CharIndex, char)> {
         let PeekState::Present(idx, char) = self.peek else {
             return Err(self.peek.err());
@@ -210,6 +380,29 @@ impl<'a> LexerStream<'a> {
         Ok((idx, char))
     }
 
+    /// Peeks `n` characters ahead of the current position (`n == 0` is
+    /// equivalent to `peek`) without consuming them. Buffered lookahead
+    /// is cached in a ring so repeated calls for the same `n`, or calls
+    /// for smaller `n`, don't re-scan the source.
+    pub fn peek_n(&mut self, n: usize) -> LexerResult<(CharIndex, char)> {
+        if n == 0 {
+            return self.peek();
+        }
+
+        while self.lookahead.len() < n {
+            let state = match self.chars.next() {
+                Some((idx, char)) => PeekState::Present(idx, char),
+                None => PeekState::Eof(self.chars.index()),
+            };
+            self.lookahead.push_back((state, self.chars.index()));
+        }
+
+        match self.lookahead[n - 1].0 {
+            PeekState::Present(idx, char) => Ok((idx, char)),
+            PeekState::Eof(idx) => Err(LexerError::eof(idx)),
+        }
+    }
+
     pub fn eat<const C: char>(&mut self) -> LexerResult<char> {
         self.advance::<SpecificChar<C>>()
     }
@@ -227,11 +420,19 @@ impl<'a> LexerStream<'a> {
             return Err(LexerError::incorrect_char(Some(c), idx, s));
         }
 
-        self.peek = match self.chars.next() {
-            Some((idx, char)) => PeekState::Present(idx, char),
-            None => PeekState::Eof(idx.advance_num(1)),
-        };
-        self.end = self.chars.index();
+        match self.lookahead.pop_front() {
+            Some((state, end)) => {
+                self.peek = state;
+                self.end = end;
+            }
+            None => {
+                self.peek = match self.chars.next() {
+                    Some((idx, char)) => PeekState::Present(idx, char),
+                    None => PeekState::Eof(idx.advance_num(1)),
+                };
+                self.end = self.chars.index();
+            }
+        }
 
         Ok(c)
     }
@@ -263,6 +464,56 @@ impl LexerError {
             position,
         }
     }
+
+    pub fn empty_number(position: CharIndex) -> Self {
+        Self {
+            err: LexerErrorType::EmptyNumberLiteral,
+            position,
+        }
+    }
+
+    pub fn extra_dot(position: CharIndex) -> Self {
+        Self {
+            err: LexerErrorType::ExtraDotInNumberLiteral,
+            position,
+        }
+    }
+
+    pub fn malformed_number(position: CharIndex, reason: impl Into<String>) -> Self {
+        Self {
+            err: LexerErrorType::MalformedNumber(reason.into()),
+            position,
+        }
+    }
+
+    pub fn unterminated_string(position: CharIndex) -> Self {
+        Self {
+            err: LexerErrorType::UnterminatedString,
+            position,
+        }
+    }
+
+    pub fn malformed_escape(position: CharIndex) -> Self {
+        Self {
+            err: LexerErrorType::MalformedEscapeSequence,
+            position,
+        }
+    }
+
+    pub fn invalid_unicode_scalar(position: CharIndex) -> Self {
+        Self {
+            err: LexerErrorType::InvalidUnicodeScalar,
+            position,
+        }
+    }
+
+    pub fn position(&self) -> CharIndex {
+        self.position
+    }
+
+    pub fn kind(&self) -> &LexerErrorType {
+        &self.err
+    }
 }
 
 impl Display for LexerError {
@@ -277,6 +528,18 @@ pub enum LexerErrorType {
     IncorrectChar(Option<char>, String),
     #[error("encountered EOF")]
     EOF,
+    #[error("given empty number literal")]
+    EmptyNumberLiteral,
+    #[error("extra dot in number literal")]
+    ExtraDotInNumberLiteral,
+    #[error("malformed number literal: {0}")]
+    MalformedNumber(String),
+    #[error("unterminated string literal")]
+    UnterminatedString,
+    #[error("malformed escape sequence")]
+    MalformedEscapeSequence,
+    #[error("invalid unicode scalar in \\u{{...}} escape")]
+    InvalidUnicodeScalar,
 }
 
 #[cfg(test)]
@@ -304,4 +567,18 @@ mod tests {
 
         assert_eq!(received, expected)
     }
+
+    #[test]
+    fn peek_n_looks_ahead_without_consuming() {
+        let mut v = LexerStream::new(IndexedCharIter::new("abcd".chars()));
+        assert_eq!(v.peek_n(1).unwrap().1, 'b');
+        assert_eq!(v.peek_n(2).unwrap().1, 'c');
+
+        // None of those peeks should have consumed anything.
+        assert_eq!(v.advance::<AnyChar>().unwrap(), 'a');
+        assert_eq!(v.advance::<AnyChar>().unwrap(), 'b');
+        assert_eq!(v.advance::<AnyChar>().unwrap(), 'c');
+        assert_eq!(v.advance::<AnyChar>().unwrap(), 'd');
+        assert!(v.advance::<AnyChar>().is_err());
+    }
 }
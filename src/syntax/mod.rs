@@ -0,0 +1,4 @@
+pub mod ast;
+pub mod diagnostic;
+pub mod lexer;
+pub mod token;
@@ -0,0 +1,507 @@
+use std::collections::VecDeque;
+
+use super::ast::{Node, Span};
+use super::lexer::{
+    matchers::{
+        AlphaChar, AlphaNumericChar, AnyChar, BinaryDigitChar, HexDigitChar, NumericChar,
+        OctalDigitChar, SpecificChar,
+    },
+    CharIndex, LexerError, LexerResult, LexerStream,
+};
+
+/// A single lexical token. Unlike the char-level scanning every
+/// `Parseable` used to do itself, `TokenStream` is the one place that
+/// knows how to turn source characters into these.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Number(f64),
+    Ident(String),
+    Operator(char),
+    Paren(char),
+    Str(String),
+    Eof,
+}
+
+/// Lazily tokenizes an underlying `LexerStream`, producing spanned
+/// tokens on demand. Keeps a small ring buffer of lookahead tokens so
+/// `peek`/`next` behave like the char-level `LexerStream::peek`/
+/// `advance` pair, and `peek_n` like `LexerStream::peek_n`.
+pub struct TokenStream<'a> {
+    lexer: LexerStream<'a>,
+    /// Each buffered token paired with whether whitespace skipped
+    /// just before it crossed a `\n` (see `crossed_newline`).
+    peeked: VecDeque<(bool, Node<Token>)>,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(lexer: LexerStream<'a>) -> Self {
+        Self {
+            lexer,
+            peeked: VecDeque::new(),
+        }
+    }
+
+    pub fn peek(&mut self) -> LexerResult<&Node<Token>> {
+        self.peek_n(0)
+    }
+
+    /// The underlying lexer's current read position, as a zero-width
+    /// span. Used by `Parser::err` to report a diagnostic's position
+    /// once token mode is in use.
+    pub fn span(&self) -> Span {
+        let pos = self.lexer.position();
+        Span::new(pos, pos)
+    }
+
+    /// Peeks `n` tokens ahead (`n == 0` is the immediate next token)
+    /// without consuming them, filling the buffer as needed.
+    pub fn peek_n(&mut self, n: usize) -> LexerResult<&Node<Token>> {
+        self.fill_to(n)?;
+        Ok(&self.peeked[n].1)
+    }
+
+    /// Whether a `\n` was crossed between the previous token and the
+    /// one `n` ahead. Used for automatic statement separation.
+    pub fn newline_before(&mut self, n: usize) -> LexerResult<bool> {
+        self.fill_to(n)?;
+        Ok(self.peeked[n].0)
+    }
+
+    pub fn next(&mut self) -> LexerResult<Node<Token>> {
+        if let Some((_, tok)) = self.peeked.pop_front() {
+            return Ok(tok);
+        }
+        Ok(self.lex_one()?.1)
+    }
+
+    fn fill_to(&mut self, n: usize) -> LexerResult<()> {
+        while self.peeked.len() <= n {
+            let tok = self.lex_one()?;
+            self.peeked.push_back(tok);
+        }
+        Ok(())
+    }
+
+    fn lex_one(&mut self) -> LexerResult<(bool, Node<Token>)> {
+        let crossed_newline = self.lexer.crossed_newline();
+        // Reset once per token, same as `crossed_newline` above: a
+        // multi-char scan checks `crossed_whitespace_pending` as it
+        // goes, so it needs to start this token seeing only whitespace
+        // crossed *during* its own scan, not whitespace that separated
+        // it from the previous token.
+        self.lexer.crossed_whitespace();
+        Ok((crossed_newline, self.lex_one_token()?))
+    }
+
+    fn lex_one_token(&mut self) -> LexerResult<Node<Token>> {
+        let (_, c) = match self.lexer.peek() {
+            Ok(pair) => pair,
+            Err(e) if e.is_eof() => return Ok(Node::new(Token::Eof, self.lexer.span())),
+            Err(e) => return Err(e),
+        };
+
+        if c.is_numeric() {
+            return self.lex_number();
+        }
+        if c.is_alphabetic() || c == '_' {
+            return self.lex_ident();
+        }
+        match c {
+            '(' | ')' => {
+                self.lexer.advance::<AnyChar>()?;
+                Ok(Node::new(Token::Paren(c), self.lexer.span()))
+            }
+            '+' | '-' | '*' | '/' => {
+                self.lexer.advance::<AnyChar>()?;
+                Ok(Node::new(Token::Operator(c), self.lexer.span()))
+            }
+            '"' => self.lex_string(),
+            _ => {
+                self.lexer.advance::<AnyChar>()?;
+                Ok(Node::new(Token::Operator(c), self.lexer.span()))
+            }
+        }
+    }
+
+    fn lex_number(&mut self) -> LexerResult<Node<Token>> {
+        let (start, first) = self.lexer.peek()?;
+
+        if first == '0' {
+            let base = self.lexer.peek_n(1).ok().map(|(_, c)| c);
+            // `peek_n` reads past the `0`, so whitespace right after it
+            // is already flagged as crossed; that whitespace ends the
+            // literal at the `0`.
+            if self.lexer.crossed_whitespace_pending() {
+                self.lexer.advance::<AnyChar>()?;
+                return Ok(Node::new(Token::Number(0.0), self.lexer.span()));
+            }
+            match base {
+                Some('x' | 'X') => return self.lex_radix_number::<HexDigitChar>(start, 16),
+                Some('b' | 'B') => return self.lex_radix_number::<BinaryDigitChar>(start, 2),
+                Some('o' | 'O') => return self.lex_radix_number::<OctalDigitChar>(start, 8),
+                _ => {}
+            }
+        }
+
+        self.lex_decimal_number(start)
+    }
+
+    /// Scans `0x`/`0b`/`0o` integer literals; `C` is the digit matcher
+    /// for `radix`.
+    fn lex_radix_number<C: super::lexer::CharMatcher>(
+        &mut self,
+        start: CharIndex,
+        radix: u32,
+    ) -> LexerResult<Node<Token>> {
+        self.lexer.advance::<AnyChar>()?; // '0'
+        self.lexer.advance::<AnyChar>()?; // x / b / o
+
+        let mut digits = String::new();
+        while !self.lexer.crossed_whitespace_pending() {
+            match self.lexer.advance::<C>() {
+                Ok(c) => digits.push(c),
+                Err(_) => break,
+            }
+        }
+
+        if digits.is_empty() {
+            return Err(LexerError::malformed_number(
+                start,
+                format!("expected digits after base-{radix} prefix"),
+            ));
+        }
+
+        let val = i64::from_str_radix(&digits, radix)
+            .map_err(|_| LexerError::malformed_number(start, "numeric literal out of range"))?
+            as f64;
+        Ok(Node::new(Token::Number(val), self.lexer.span()))
+    }
+
+    /// Scans a decimal literal, including an optional `1.5e-3`-style
+    /// exponent. `EmptyNumberLiteral`/`ExtraDotInNumberLiteral` keep
+    /// their original meaning from the char-level `Number::parse`.
+    fn lex_decimal_number(&mut self, start: CharIndex) -> LexerResult<Node<Token>> {
+        let mut chars = String::new();
+        while !self.lexer.crossed_whitespace_pending() {
+            match self.lexer.advance::<NumericChar>() {
+                Ok(c) => chars.push(c),
+                Err(_) => break,
+            }
+        }
+
+        if !self.lexer.crossed_whitespace_pending() && matches!(self.lexer.peek(), Ok((_, '.'))) {
+            self.lexer.advance::<SpecificChar<'.'>>()?;
+            chars.push('.');
+
+            while !self.lexer.crossed_whitespace_pending() {
+                match self.lexer.advance::<NumericChar>() {
+                    Ok(c) => chars.push(c),
+                    Err(_) => break,
+                }
+            }
+
+            if !self.lexer.crossed_whitespace_pending() && matches!(self.lexer.peek(), Ok((_, '.')))
+            {
+                return Err(LexerError::extra_dot(start));
+            }
+        }
+
+        if !self.lexer.crossed_whitespace_pending() && matches!(self.lexer.peek(), Ok((_, 'e' | 'E')))
+        {
+            let (_, marker) = self.lexer.peek().unwrap();
+            self.lexer.advance::<AnyChar>()?;
+            chars.push(marker);
+
+            if !self.lexer.crossed_whitespace_pending()
+                && matches!(self.lexer.peek(), Ok((_, '+' | '-')))
+            {
+                let (_, sign) = self.lexer.peek().unwrap();
+                self.lexer.advance::<AnyChar>()?;
+                chars.push(sign);
+            }
+
+            let mut exponent_digits = String::new();
+            while !self.lexer.crossed_whitespace_pending() {
+                match self.lexer.advance::<NumericChar>() {
+                    Ok(c) => {
+                        exponent_digits.push(c);
+                        chars.push(c);
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            if exponent_digits.is_empty() {
+                return Err(LexerError::malformed_number(
+                    start,
+                    "expected digits after exponent marker",
+                ));
+            }
+
+            if !self.lexer.crossed_whitespace_pending()
+                && matches!(self.lexer.peek(), Ok((_, 'e' | 'E')))
+            {
+                return Err(LexerError::malformed_number(
+                    start,
+                    "duplicate exponent marker in number literal",
+                ));
+            }
+        }
+
+        if chars.is_empty() || chars == "." {
+            return Err(LexerError::empty_number(start));
+        }
+
+        let val = chars
+            .parse::<f64>()
+            .map_err(|_| LexerError::malformed_number(start, "invalid number literal"))?;
+        Ok(Node::new(Token::Number(val), self.lexer.span()))
+    }
+
+    fn lex_ident(&mut self) -> LexerResult<Node<Token>> {
+        let mut chars = String::new();
+        chars.push(self.lexer.advance::<AlphaChar>()?);
+        while !self.lexer.crossed_whitespace_pending() {
+            match self.lexer.advance::<AlphaNumericChar>() {
+                Ok(c) => chars.push(c),
+                Err(_) => break,
+            }
+        }
+        Ok(Node::new(Token::Ident(chars), self.lexer.span()))
+    }
+
+    fn lex_string(&mut self) -> LexerResult<Node<Token>> {
+        let contents = scan_string_literal(&mut self.lexer)?;
+        Ok(Node::new(Token::Str(contents), self.lexer.span()))
+    }
+}
+
+/// Scans a double-quoted string literal, decoding its escape sequences.
+/// Used by `TokenStream::lex_string` to produce `Token::Str`.
+pub(crate) fn scan_string_literal(lexer: &mut LexerStream) -> LexerResult<String> {
+    lexer.advance::<SpecificChar<'"'>>()?;
+
+    let mut out = String::new();
+    loop {
+        let (_, c) = match lexer.peek() {
+            Ok(pair) => pair,
+            Err(e) if e.is_eof() => return Err(LexerError::unterminated_string(e.position())),
+            Err(e) => return Err(e),
+        };
+
+        if c == '"' {
+            lexer.advance::<SpecificChar<'"'>>()?;
+            break;
+        } else if c == '\\' {
+            lexer.advance::<SpecificChar<'\\'>>()?;
+            out.push(decode_escape(lexer)?);
+        } else {
+            out.push(lexer.advance::<AnyChar>()?);
+        }
+    }
+
+    Ok(out)
+}
+
+fn decode_escape(lexer: &mut LexerStream) -> LexerResult<char> {
+    let (idx, c) = match lexer.peek() {
+        Ok(pair) => pair,
+        Err(e) if e.is_eof() => return Err(LexerError::unterminated_string(e.position())),
+        Err(e) => return Err(e),
+    };
+
+    match c {
+        'n' => {
+            lexer.advance::<SpecificChar<'n'>>()?;
+            Ok('\n')
+        }
+        't' => {
+            lexer.advance::<SpecificChar<'t'>>()?;
+            Ok('\t')
+        }
+        'r' => {
+            lexer.advance::<SpecificChar<'r'>>()?;
+            Ok('\r')
+        }
+        '\\' => {
+            lexer.advance::<SpecificChar<'\\'>>()?;
+            Ok('\\')
+        }
+        '"' => {
+            lexer.advance::<SpecificChar<'"'>>()?;
+            Ok('"')
+        }
+        '\'' => {
+            lexer.advance::<SpecificChar<'\''>>()?;
+            Ok('\'')
+        }
+        '0' => {
+            lexer.advance::<SpecificChar<'0'>>()?;
+            Ok('\0')
+        }
+        'x' => {
+            lexer.advance::<SpecificChar<'x'>>()?;
+            let mut digits = String::new();
+            for _ in 0..2 {
+                let c = lexer
+                    .advance::<AnyChar>()
+                    .map_err(|_| LexerError::malformed_escape(idx))?;
+                digits.push(c);
+            }
+            let value =
+                u8::from_str_radix(&digits, 16).map_err(|_| LexerError::malformed_escape(idx))?;
+            Ok(value as char)
+        }
+        'u' => {
+            lexer.advance::<SpecificChar<'u'>>()?;
+            lexer
+                .advance::<SpecificChar<'{'>>()
+                .map_err(|_| LexerError::malformed_escape(idx))?;
+
+            let mut digits = String::new();
+            loop {
+                match lexer.peek() {
+                    Ok((_, '}')) => break,
+                    Ok((_, d)) if d.is_ascii_hexdigit() && digits.len() < 6 => {
+                        digits.push(d);
+                        lexer.advance::<AnyChar>()?;
+                    }
+                    _ => return Err(LexerError::malformed_escape(idx)),
+                }
+            }
+            lexer
+                .advance::<SpecificChar<'}'>>()
+                .map_err(|_| LexerError::malformed_escape(idx))?;
+
+            if digits.is_empty() {
+                return Err(LexerError::malformed_escape(idx));
+            }
+
+            // `char::from_u32` rejects both the D800-DFFF surrogate
+            // range and scalars above U+10FFFF for us.
+            let scalar = u32::from_str_radix(&digits, 16)
+                .map_err(|_| LexerError::invalid_unicode_scalar(idx))?;
+            char::from_u32(scalar).ok_or_else(|| LexerError::invalid_unicode_scalar(idx))
+        }
+        _ => Err(LexerError::malformed_escape(idx)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::lexer::{IndexedCharIter, LexerStream};
+
+    use super::{Token, TokenStream};
+
+    #[test]
+    fn peek_n_looks_past_multiple_tokens_without_consuming() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new("1 + 2".chars())));
+
+        assert_eq!(**stream.peek_n(0).unwrap(), Token::Number(1.0));
+        assert_eq!(**stream.peek_n(1).unwrap(), Token::Operator('+'));
+        assert_eq!(**stream.peek_n(2).unwrap(), Token::Number(2.0));
+
+        assert_eq!(*stream.next().unwrap(), Token::Number(1.0));
+        assert_eq!(*stream.next().unwrap(), Token::Operator('+'));
+        assert_eq!(*stream.next().unwrap(), Token::Number(2.0));
+        assert_eq!(*stream.next().unwrap(), Token::Eof);
+    }
+
+    #[test]
+    fn string_literals_decode_escape_sequences() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            r#""a\nb\tc\u{41}""#.chars(),
+        )));
+        assert_eq!(*stream.next().unwrap(), Token::Str("a\nb\tc\u{41}".to_string()));
+    }
+
+    #[test]
+    fn unterminated_string_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            "\"abc".chars(),
+        )));
+        assert!(stream.next().unwrap_err().kind().to_string().contains("unterminated"));
+    }
+
+    #[test]
+    fn hex_escape_decodes_a_byte() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            r#""\x41\x42""#.chars(),
+        )));
+        assert_eq!(*stream.next().unwrap(), Token::Str("AB".to_string()));
+    }
+
+    #[test]
+    fn surrogate_codepoint_escape_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            r#""\u{D800}""#.chars(),
+        )));
+        assert!(stream
+            .next()
+            .unwrap_err()
+            .kind()
+            .to_string()
+            .contains("invalid unicode scalar"));
+    }
+
+    #[test]
+    fn out_of_range_codepoint_escape_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            r#""\u{110000}""#.chars(),
+        )));
+        assert!(stream
+            .next()
+            .unwrap_err()
+            .kind()
+            .to_string()
+            .contains("invalid unicode scalar"));
+    }
+
+    #[test]
+    fn unrecognized_escape_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new(
+            r#""\q""#.chars(),
+        )));
+        assert!(stream
+            .next()
+            .unwrap_err()
+            .kind()
+            .to_string()
+            .contains("malformed escape"));
+    }
+
+    #[test]
+    fn hex_binary_and_octal_literals() {
+        let mut stream =
+            TokenStream::new(LexerStream::new(IndexedCharIter::new("0xFF 0b101 0o17".chars())));
+        assert_eq!(*stream.next().unwrap(), Token::Number(255.0));
+        assert_eq!(*stream.next().unwrap(), Token::Number(5.0));
+        assert_eq!(*stream.next().unwrap(), Token::Number(15.0));
+    }
+
+    #[test]
+    fn radix_prefix_with_no_digits_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new("0x".chars())));
+        assert!(stream.next().is_err());
+    }
+
+    #[test]
+    fn scientific_notation_literals() {
+        let mut stream =
+            TokenStream::new(LexerStream::new(IndexedCharIter::new("1.5e-3 2E2".chars())));
+        assert_eq!(*stream.next().unwrap(), Token::Number(1.5e-3));
+        assert_eq!(*stream.next().unwrap(), Token::Number(2E2));
+    }
+
+    #[test]
+    fn exponent_marker_with_no_digits_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new("1e".chars())));
+        assert!(stream.next().is_err());
+    }
+
+    #[test]
+    fn duplicate_exponent_marker_is_an_error() {
+        let mut stream = TokenStream::new(LexerStream::new(IndexedCharIter::new("1e2e3".chars())));
+        assert!(stream.next().is_err());
+    }
+}